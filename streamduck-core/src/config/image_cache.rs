@@ -0,0 +1,118 @@
+//! LRU access tracking for decoded, in-memory image collections
+//!
+//! [ImageCollection](crate::ImageCollection) stays a plain keyed map of
+//! decoded [SDImage](crate::images::SDImage)s; this module only tracks, per
+//! device serial, the order images were last accessed in, so [Config] knows
+//! which key to evict once a collection grows past its configured budget.
+//! Built on [clru], the same LRU crate microdeck uses, rather than a
+//! hand-rolled recency queue.
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use clru::CLruCache;
+
+/// Effectively unbounded: [Config] enforces the real budget itself by
+/// comparing [ImageCollection](crate::ImageCollection)'s length against
+/// [Config::image_cache_size](super::Config::image_cache_size) and popping
+/// the LRU key on overflow, so the cache here only needs to track order
+const UNBOUNDED: NonZeroUsize = NonZeroUsize::MAX;
+
+/// Tracks access order of image keys per device serial
+#[derive(Default)]
+pub struct ImageAccessOrder {
+    order: HashMap<String, CLruCache<String, ()>>,
+}
+
+impl ImageAccessOrder {
+    /// Marks `key` as most-recently-used for `serial`
+    pub fn touch(&mut self, serial: &str, key: &str) {
+        self.order.entry(serial.to_string())
+            .or_insert_with(|| CLruCache::new(UNBOUNDED))
+            .put(key.to_string(), ());
+    }
+
+    /// Removes `key` from tracking for `serial`, e.g. once it's evicted
+    pub fn forget(&mut self, serial: &str, key: &str) {
+        if let Some(cache) = self.order.get_mut(serial) {
+            cache.pop(key);
+        }
+    }
+
+    /// Pops the least-recently-used key tracked for `serial`, if any
+    pub fn pop_lru(&mut self, serial: &str) -> Option<String> {
+        self.order.get_mut(serial)?.pop_lru().map(|(key, _)| key)
+    }
+
+    /// Drops all tracked keys for `serial`, e.g. once its collection is cleared
+    pub fn clear(&mut self, serial: &str) {
+        self.order.remove(serial);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pop_lru_returns_least_recently_touched_key_first() {
+        let mut order = ImageAccessOrder::default();
+        order.touch("ABC123", "one");
+        order.touch("ABC123", "two");
+        order.touch("ABC123", "three");
+
+        assert_eq!(order.pop_lru("ABC123"), Some("one".to_string()));
+        assert_eq!(order.pop_lru("ABC123"), Some("two".to_string()));
+        assert_eq!(order.pop_lru("ABC123"), Some("three".to_string()));
+        assert_eq!(order.pop_lru("ABC123"), None);
+    }
+
+    #[test]
+    fn re_touching_a_key_moves_it_back_to_most_recently_used() {
+        let mut order = ImageAccessOrder::default();
+        order.touch("ABC123", "one");
+        order.touch("ABC123", "two");
+        order.touch("ABC123", "one");
+
+        assert_eq!(order.pop_lru("ABC123"), Some("two".to_string()));
+        assert_eq!(order.pop_lru("ABC123"), Some("one".to_string()));
+    }
+
+    #[test]
+    fn forget_removes_a_key_so_it_is_never_popped() {
+        let mut order = ImageAccessOrder::default();
+        order.touch("ABC123", "one");
+        order.touch("ABC123", "two");
+        order.forget("ABC123", "one");
+
+        assert_eq!(order.pop_lru("ABC123"), Some("two".to_string()));
+        assert_eq!(order.pop_lru("ABC123"), None);
+    }
+
+    #[test]
+    fn forget_on_an_untracked_serial_is_a_no_op() {
+        let mut order = ImageAccessOrder::default();
+        order.forget("NOPE", "one");
+    }
+
+    #[test]
+    fn clear_drops_every_key_tracked_for_a_serial() {
+        let mut order = ImageAccessOrder::default();
+        order.touch("ABC123", "one");
+        order.touch("ABC123", "two");
+        order.touch("XYZ789", "three");
+        order.clear("ABC123");
+
+        assert_eq!(order.pop_lru("ABC123"), None);
+        assert_eq!(order.pop_lru("XYZ789"), Some("three".to_string()));
+    }
+
+    #[test]
+    fn serials_are_tracked_independently() {
+        let mut order = ImageAccessOrder::default();
+        order.touch("ABC123", "one");
+        order.touch("XYZ789", "one");
+        order.forget("ABC123", "one");
+
+        assert_eq!(order.pop_lru("ABC123"), None);
+        assert_eq!(order.pop_lru("XYZ789"), Some("one".to_string()));
+    }
+}
@@ -0,0 +1,67 @@
+//! Recursive merging of a user override layer over a base config
+use serde_json::Value;
+
+/// Recursively merges `overlay` into `base`: object keys in `overlay` overwrite
+/// or add to the matching key in `base` (recursing into nested objects, so
+/// e.g. `plugin_data` and `images` merge per-key rather than being replaced
+/// wholesale), while any non-object value, including arrays, simply replaces
+/// whatever was in `base`
+pub fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, overlay_value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(base_value) => deep_merge(base_value, overlay_value),
+                    None => { base_map.insert(key, overlay_value); }
+                }
+            }
+        }
+        (base_slot, overlay_value) => *base_slot = overlay_value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn top_level_scalar_is_overwritten() {
+        let mut base = json!({ "brightness": 50, "serial": "ABC" });
+        deep_merge(&mut base, json!({ "brightness": 80 }));
+
+        assert_eq!(base, json!({ "brightness": 80, "serial": "ABC" }));
+    }
+
+    #[test]
+    fn plugin_data_merges_per_key_instead_of_replacing() {
+        let mut base = json!({ "plugin_data": { "a": 1, "b": 2 } });
+        deep_merge(&mut base, json!({ "plugin_data": { "b": 99, "c": 3 } }));
+
+        assert_eq!(base, json!({ "plugin_data": { "a": 1, "b": 99, "c": 3 } }));
+    }
+
+    #[test]
+    fn images_merge_per_key_too() {
+        let mut base = json!({ "images": { "img1": "aaaa" } });
+        deep_merge(&mut base, json!({ "images": { "img2": "bbbb" } }));
+
+        assert_eq!(base, json!({ "images": { "img1": "aaaa", "img2": "bbbb" } }));
+    }
+
+    #[test]
+    fn arrays_are_replaced_wholesale_not_merged() {
+        let mut base = json!({ "layout": [1, 2, 3] });
+        deep_merge(&mut base, json!({ "layout": [9] }));
+
+        assert_eq!(base, json!({ "layout": [9] }));
+    }
+
+    #[test]
+    fn new_keys_are_added() {
+        let mut base = json!({ "serial": "ABC" });
+        deep_merge(&mut base, json!({ "brightness": 10 }));
+
+        assert_eq!(base, json!({ "serial": "ABC", "brightness": 10 }));
+    }
+}
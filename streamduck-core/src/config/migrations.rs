@@ -0,0 +1,110 @@
+//! Migrations for on-disk [DeviceConfig](super::DeviceConfig) files
+//!
+//! Configs are versioned so the layout of [RawButtonPanel](crate::core::RawButtonPanel)
+//! or `plugin_data` can change over time without orphaning files that were
+//! written by an older version of the crate. Each migration is tagged with the
+//! version it upgrades *from*; [migrate] walks the list in order, applying
+//! every migration whose `from` falls between the file's version and
+//! [CURRENT_VERSION].
+use serde_json::Value;
+
+/// Current version of the [DeviceConfig](super::DeviceConfig) schema
+pub const CURRENT_VERSION: u32 = 2;
+
+/// A single migration step, tagged with the version it upgrades from
+pub struct Migration {
+    /// Version this migration expects as input
+    pub from: u32,
+    /// Function that performs the upgrade
+    pub migrate: fn(Value) -> Value,
+}
+
+/// Ordered list of migrations to bring a config up to [CURRENT_VERSION]
+pub static MIGRATIONS: &[Migration] = &[
+    Migration { from: 0, migrate: migrate_0_to_1 },
+    Migration { from: 1, migrate: migrate_1_to_2 },
+];
+
+/// Applies every migration whose `from` is between `version` and [CURRENT_VERSION]
+/// to `value`, in order, and returns the migrated value
+pub fn migrate(mut value: Value, version: u32) -> Value {
+    for migration in MIGRATIONS {
+        if migration.from >= version && migration.from < CURRENT_VERSION {
+            value = (migration.migrate)(value);
+        }
+    }
+
+    value
+}
+
+/// Legacy configs predate the `version` field entirely; this just stamps them
+/// with an explicit version so later migrations have something to key off
+fn migrate_0_to_1(mut value: Value) -> Value {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), Value::from(1));
+    }
+
+    value
+}
+
+/// `brightness` is a percentage and the schema added in chunk0-6 caps it at
+/// 100, but the field itself has always been a bare `u8` with no enforced
+/// range, so earlier releases could happily write (and load) values above
+/// that. Clamp any out-of-range legacy value instead of letting it fail
+/// schema validation outright
+fn migrate_1_to_2(mut value: Value) -> Value {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), Value::from(2));
+
+        if let Some(brightness) = object.get("brightness").and_then(Value::as_u64) {
+            object.insert("brightness".to_string(), Value::from(brightness.min(100)));
+        }
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn legacy_file_runs_every_migration_in_order() {
+        let legacy = json!({ "brightness": 150 });
+
+        let migrated = migrate(legacy, 0);
+
+        assert_eq!(migrated["version"], json!(CURRENT_VERSION));
+        assert_eq!(migrated["brightness"], json!(100));
+    }
+
+    #[test]
+    fn already_migrated_file_is_left_untouched() {
+        let current = json!({ "version": CURRENT_VERSION, "brightness": 50 });
+
+        let migrated = migrate(current.clone(), CURRENT_VERSION);
+
+        assert_eq!(migrated, current);
+    }
+
+    #[test]
+    fn partially_migrated_file_only_runs_remaining_migrations() {
+        // Already at version 1: migrate_0_to_1 must not run again, only migrate_1_to_2
+        let value = json!({ "version": 1, "brightness": 200 });
+
+        let migrated = migrate(value, 1);
+
+        assert_eq!(migrated["version"], json!(CURRENT_VERSION));
+        assert_eq!(migrated["brightness"], json!(100));
+    }
+
+    #[test]
+    fn in_range_brightness_is_left_alone() {
+        let value = json!({ "brightness": 42 });
+
+        let migrated = migrate(value, 0);
+
+        assert_eq!(migrated["brightness"], json!(42));
+    }
+}
@@ -0,0 +1,747 @@
+//! Core and device configs
+mod image_cache;
+mod merge;
+mod migrations;
+mod patterns;
+mod persist;
+mod schema;
+pub mod watcher;
+
+use image_cache::ImageAccessOrder;
+pub use schema::ValidationIssue;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use image::{DynamicImage};
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use crate::core::RawButtonPanel;
+use serde_json::Value;
+use streamdeck::Kind;
+use crate::ImageCollection;
+use crate::images::{SDImage, SDSerializedImage};
+use crate::util::{hash_image, hash_str};
+use crate::thread::util::resize_for_streamdeck;
+
+pub const DEFAULT_POOL_RATE: u32 = 1000;
+pub const DEFAULT_RECONNECT_TIME: f32 = 1.0;
+pub const DEFAULT_CONFIG_PATH: &'static str = "devices";
+pub const DEFAULT_PLUGIN_PATH: &'static str = "plugins";
+pub const DEFAULT_PLUGIN_SETTINGS_PATH: &'static str = "global.json";
+/// Default maximum number of decoded images kept in memory per device
+pub const DEFAULT_IMAGE_CACHE_SIZE: usize = 128;
+
+/// Reference counted [DeviceConfig]
+pub type UniqueDeviceConfig = Arc<RwLock<DeviceConfig>>;
+
+/// Struct to keep daemon settings
+#[derive(Serialize, Deserialize, Default)]
+pub struct Config {
+    /// Frequency of streamdeck event pooling
+    pool_rate: Option<u32>,
+    /// Frequency of checks for disconnected devices
+    reconnect_rate: Option<f32>,
+    /// Path to device configs
+    device_config_path: Option<PathBuf>,
+    /// Path to plugins
+    plugin_path: Option<PathBuf>,
+    /// Path to plugin settings json
+    plugin_settings_path: Option<PathBuf>,
+    /// Maximum number of decoded images kept in memory per device
+    image_cache_size: Option<usize>,
+    /// Whether to watch config files on disk and hot-reload them on change
+    watch: Option<bool>,
+
+    /// Paths we just wrote ourselves, so the file watcher can skip reacting
+    /// to its own writes
+    #[serde(skip)]
+    own_writes: Mutex<HashSet<PathBuf>>,
+
+    #[serde(skip)]
+    pub plugin_settings: RwLock<HashMap<String, Value>>,
+
+    /// Currently loaded device configs
+    #[serde(skip)]
+    pub loaded_configs: RwLock<HashMap<String, UniqueDeviceConfig>>,
+
+    /// Default device configs, keyed by the serial pattern they apply to (see [patterns])
+    #[serde(skip)]
+    pub default_configs: RwLock<HashMap<String, DeviceConfig>>,
+
+    /// Currently loaded image collections
+    #[serde(skip)]
+    pub loaded_images: RwLock<HashMap<String, ImageCollection>>,
+
+    /// Access order of cached images, used to evict the least-recently-used
+    /// entry once a device's collection exceeds [Self::image_cache_size]
+    #[serde(skip)]
+    image_access_order: RwLock<ImageAccessOrder>,
+}
+
+#[allow(dead_code)]
+impl Config {
+    /// Reads config and retrieves config struct
+    pub fn get() -> Config {
+        let config: Config = if let Ok(content) = fs::read_to_string("config.toml") {
+            if let Ok(config) = toml::from_str(&content) {
+                config
+            } else {
+                Default::default()
+            }
+        } else {
+            Default::default()
+        };
+
+        config.load_plugin_settings();
+
+        config
+    }
+
+    /// Pool rate, defaults to [DEFAULT_POOL_RATE] if not set
+    pub fn pool_rate(&self) -> u32 {
+        self.pool_rate.unwrap_or(DEFAULT_POOL_RATE)
+    }
+
+    /// Reconnect rate, defaults to [DEFAULT_RECONNECT_TIME] if not set
+    pub fn reconnect_rate(&self) -> f32 {
+        self.reconnect_rate.unwrap_or(DEFAULT_RECONNECT_TIME)
+    }
+
+    /// Device config path, defaults to [DEFAULT_CONFIG_PATH] if not set
+    pub fn device_config_path(&self) -> PathBuf {
+        self.device_config_path.clone().unwrap_or(PathBuf::from(DEFAULT_CONFIG_PATH))
+    }
+
+    /// Plugin folder path, defaults to [DEFAULT_PLUGIN_PATH] if not set
+    pub fn plugin_path(&self) -> PathBuf {
+        self.plugin_path.clone().unwrap_or(PathBuf::from(DEFAULT_PLUGIN_PATH))
+    }
+
+    /// Global config path, defaults to [DEFAULT_PLUGIN_SETTINGS_PATH] if not set
+    pub fn plugin_settings_path(&self) -> PathBuf {
+        self.plugin_settings_path.clone().unwrap_or(PathBuf::from(DEFAULT_PLUGIN_SETTINGS_PATH))
+    }
+
+    /// Per-device decoded image cache budget, defaults to [DEFAULT_IMAGE_CACHE_SIZE] if not set
+    pub fn image_cache_size(&self) -> usize {
+        self.image_cache_size.unwrap_or(DEFAULT_IMAGE_CACHE_SIZE)
+    }
+
+    /// Whether the config file watcher should be enabled, defaults to disabled
+    pub fn watch(&self) -> bool {
+        self.watch.unwrap_or(false)
+    }
+
+    /// Spawns the background file watcher if [Self::watch] is enabled
+    pub fn spawn_watcher(self: &Arc<Self>) -> Option<std::thread::JoinHandle<()>> {
+        watcher::spawn(self.clone())
+    }
+
+    /// Records that `path` was just written by us, so the file watcher
+    /// ignores the resulting filesystem event instead of reloading it
+    fn mark_own_write(&self, path: &Path) {
+        self.own_writes.lock().unwrap().insert(path.to_path_buf());
+    }
+
+    /// Returns whether `path` was just written by us, forgetting it either way
+    fn forget_own_write(&self, path: &Path) -> bool {
+        self.own_writes.lock().unwrap().remove(path)
+    }
+
+    /// Loads plugin settings from file
+    pub fn load_plugin_settings(&self) {
+        if let Ok(settings) = fs::read_to_string(self.plugin_settings_path()) {
+            let mut lock = self.plugin_settings.write().unwrap();
+
+            match serde_json::from_str(&settings) {
+                Ok(vals) => *lock = vals,
+                Err(err) => log::error!("Failed to parse plugin settings: {:?}", err),
+            }
+        }
+    }
+
+    /// Retrieves plugin settings if it exists
+    pub fn get_plugin_settings<T: PluginConfig + DeserializeOwned>(&self) -> Option<T> {
+        let lock = self.plugin_settings.read().unwrap();
+        Some(serde_json::from_value(lock.get(T::NAME)?.clone()).ok()?)
+    }
+
+    /// Sets plugin settings
+    pub fn set_plugin_settings<T: PluginConfig + Serialize>(&self, value: T) {
+        let mut lock = self.plugin_settings.write().unwrap();
+        lock.insert(T::NAME.to_string(), serde_json::to_value(value).unwrap());
+        drop(lock);
+
+        self.write_plugin_settings();
+    }
+
+    /// Writes plugin settings to file, atomically
+    pub fn write_plugin_settings(&self) {
+        let lock = self.plugin_settings.read().unwrap();
+        let path = self.plugin_settings_path();
+        self.mark_own_write(&path);
+
+        if let Err(err) = persist::atomic_write(&path, &serde_json::to_string(lock.deref()).unwrap()) {
+            log::error!("Failed to write plugin settings: {:?}", err);
+        }
+    }
+
+    /// Reloads device config for specified serial
+    pub fn reload_device_config(&self, serial: &str) -> Result<(), ConfigError> {
+        // Clearing image collection to make sure it's fresh for reload
+        self.get_image_collection(serial).write().unwrap().clear();
+        self.image_access_order.write().unwrap().clear(serial);
+
+        let mut devices = self.loaded_configs.write().unwrap();
+
+        let mut path = self.device_config_path();
+        path.push(format!("{}.json", serial));
+
+        let content = fs::read_to_string(&path)?;
+        let overlay = self.user_config_overlay(serial);
+        let (device, migrated_base) = Self::parse_device_config(&content, overlay)?;
+
+        if let Some(migrated_base) = migrated_base {
+            self.mark_own_write(&path);
+            persist::atomic_write(&path, &serde_json::to_string(&migrated_base).unwrap())?;
+        }
+
+        if let Some(device_config) = devices.get(serial) {
+            *device_config.write().unwrap() = device;
+        } else {
+            devices.insert(serial.to_string(), Arc::new(RwLock::new(device)));
+        }
+
+        self.update_collection(devices.get(serial).unwrap());
+
+        Ok(())
+    }
+
+    /// Reloads all device configs
+    pub fn reload_device_configs(&self) -> Result<(), ConfigError> {
+        let mut devices = self.loaded_configs.write().unwrap();
+
+        let dir = fs::read_dir(self.device_config_path())?;
+
+        for item in dir {
+            let item = item?;
+            if item.path().is_file() {
+                if let Some(extension) = item.path().extension() {
+                    let file_name = item.path().file_name().and_then(|s| s.to_str()).unwrap_or("").to_string();
+
+                    // The user override layer lives next to its base config and shares
+                    // the "json" extension, but isn't a device config on its own
+                    if extension == "json" && !file_name.ends_with(".user.json") {
+                        let content = fs::read_to_string(item.path())?;
+
+                        let file_stem = item.path().file_stem().and_then(|s| s.to_str()).map(|s| s.to_string());
+                        let overlay = file_stem.as_deref().and_then(|serial| self.user_config_overlay(serial));
+
+                        let (device, migrated_base) = Self::parse_device_config(&content, overlay)?;
+                        let serial = device.serial.to_string();
+
+                        if let Some(migrated_base) = migrated_base {
+                            self.mark_own_write(&item.path());
+                            persist::atomic_write(&item.path(), &serde_json::to_string(&migrated_base).unwrap())?;
+                        }
+
+                        // A serial containing `*` is a pattern, not a real device: file it
+                        // away as a default config instead of a loaded one
+                        if serial.contains('*') {
+                            self.default_configs.write().unwrap().insert(serial, device);
+                            continue;
+                        }
+
+                        // Clearing image collection so it's fresh for reload
+                        self.get_image_collection(&device.serial).write().unwrap().clear();
+                        self.image_access_order.write().unwrap().clear(&device.serial);
+                        if let Some(device_config) = devices.get(&serial) {
+                            *device_config.write().unwrap() = device;
+                        } else {
+                            devices.insert(serial.to_string(), Arc::new(RwLock::new(device)));
+                        }
+
+                        self.update_collection(devices.get(&serial).unwrap());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Saves device config for specified serial. Only ever writes the base
+    /// file; the `<serial>.user.json` override layer is left untouched so
+    /// hand-edited overrides aren't clobbered
+    pub fn save_device_config(&self, serial: &str) -> Result<(), ConfigError> {
+        let devices = self.loaded_configs.read().unwrap();
+
+        if let Some(device) = devices.get(serial).cloned() {
+            self.update_collection(&device);
+            let mut path = self.device_config_path();
+            fs::create_dir_all(&path).ok();
+            path.push(format!("{}.json", serial));
+
+            self.mark_own_write(&path);
+            persist::atomic_write(&path, &serde_json::to_string(device.read().unwrap().deref()).unwrap())?;
+            Ok(())
+        } else {
+            Err(ConfigError::DeviceNotFound)
+        }
+    }
+
+    /// Saves device configs for all serials
+    pub fn save_device_configs(&self) -> Result<(), ConfigError> {
+        let devices = self.loaded_configs.read().unwrap();
+
+        let path = self.device_config_path();
+        fs::create_dir_all(&path).ok();
+
+        for (serial, device) in devices.iter() {
+            let device= device.clone();
+            self.update_collection(&device);
+            let mut file_path = path.clone();
+            file_path.push(format!("{}.json", serial));
+
+            self.mark_own_write(&file_path);
+            persist::atomic_write(&file_path, &serde_json::to_string(device.read().unwrap().deref()).unwrap())?;
+        }
+
+        Ok(())
+    }
+
+    /// Retrieves device config for specified serial, falling back to the first
+    /// matching [default config](Self::default_configs) if no exact one is loaded
+    pub fn get_device_config(&self, serial: &str) -> Option<UniqueDeviceConfig> {
+        if let Some(config) = self.loaded_configs.read().unwrap().get(serial).cloned() {
+            return Some(config);
+        }
+
+        self.device_config_from_default(serial)
+    }
+
+    /// Builds a fresh device config for `serial` from the most specific default
+    /// config whose pattern matches it (see [patterns::specificity]; ties break
+    /// on the pattern string itself, so the winner is deterministic rather than
+    /// depending on hash map iteration order), deep-cloning the layout,
+    /// brightness and plugin data, and registers it as that serial's loaded config
+    fn device_config_from_default(&self, serial: &str) -> Option<UniqueDeviceConfig> {
+        let default = {
+            let defaults = self.default_configs.read().unwrap();
+            let pattern = defaults.keys()
+                .filter(|pattern| patterns::matches(pattern, serial))
+                .max_by_key(|pattern| (patterns::specificity(pattern), pattern.as_str()))?;
+            defaults.get(pattern)?.clone()
+        };
+
+        let device = DeviceConfig {
+            version: migrations::CURRENT_VERSION,
+            vid: default.vid,
+            pid: default.pid,
+            serial: serial.to_string(),
+            brightness: default.brightness,
+            layout: default.layout,
+            images: Default::default(),
+            plugin_data: default.plugin_data,
+        };
+
+        let config = Arc::new(RwLock::new(device));
+        self.loaded_configs.write().unwrap().insert(serial.to_string(), config.clone());
+        Some(config)
+    }
+
+    /// Sets device config for specified serial
+    pub fn set_device_config(&self, serial: &str, config: DeviceConfig) {
+        let mut handle = self.loaded_configs.write().unwrap();
+
+        if let Some(device_config) = handle.get(serial) {
+            *device_config.write().unwrap() = config;
+        } else {
+            handle.insert(serial.to_string(), Arc::new(RwLock::new(config)));
+        }
+    }
+
+    /// Gets an array of all device configs
+    pub fn get_all_device_configs(&self) -> Vec<UniqueDeviceConfig> {
+        self.loaded_configs.read().unwrap().values().map(|x| x.clone()).collect()
+    }
+
+    /// Disables a device config, so it will not be loaded by default
+    pub fn disable_device_config(&self, serial: &str) -> bool {
+        let path = self.device_config_path();
+
+        let mut initial_path = path.clone();
+        initial_path.push(format!("{}.json", serial));
+
+        let mut new_path = path.clone();
+        new_path.push(format!("{}.json_disabled", serial));
+
+        fs::rename(initial_path, new_path).is_ok()
+    }
+
+    /// Restores device config if it exists
+    pub fn restore_device_config(&self, serial: &str) -> bool {
+        let path = self.device_config_path();
+
+        let mut initial_path = path.clone();
+        initial_path.push(format!("{}.json_disabled", serial));
+
+        let mut new_path = path.clone();
+        new_path.push(format!("{}.json", serial));
+
+        fs::rename(initial_path, new_path).is_ok()
+    }
+
+    /// Adds base64 image to device config image collection
+    pub fn add_image(&self, serial: &str, image: String) -> Option<String> {
+        if let Some(config) = self.get_device_config(serial) {
+            let mut config_handle = config.write().unwrap();
+            let identifier = hash_str(&image);
+
+            if let Ok(image) = SDImage::from_base64(&image, config_handle.kind().image_size()) {
+                config_handle.images.insert(identifier.clone(), image.into());
+                drop(config_handle);
+
+                self.update_collection(&config);
+                Some(identifier)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Encodes image to base64 and adds it to device config image collection
+    pub fn add_image_encode(&self, serial: &str, image: DynamicImage) -> Option<String> {
+        if let Some(config) = self.get_device_config(serial) {
+            let mut config_handle = config.write().unwrap();
+            let serialized_image = SDImage::SingleImage(resize_for_streamdeck(config_handle.kind().image_size(), image)).into();
+            let identifier = hash_image(&serialized_image);
+            config_handle.images.insert(identifier.clone(), serialized_image);
+            drop(config_handle);
+
+            self.update_collection(&config);
+            return Some(identifier);
+        }
+
+        None
+    }
+
+    /// Gets images from device config
+    pub fn get_images(&self, serial: &str) -> Option<HashMap<String, SDSerializedImage>> {
+        if let Some(config) = self.get_device_config(serial) {
+            let config_handle = config.read().unwrap();
+            Some(config_handle.images.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Removes image from device config
+    pub fn remove_image(&self, serial: &str, identifier: &str) -> bool {
+        if let Some(config) = self.get_device_config(serial) {
+            let mut config_handle = config.write().unwrap();
+            config_handle.images.remove(identifier);
+            drop(config_handle);
+
+            self.remove_from_collection(serial, identifier);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Syncs images with core
+    pub fn sync_images(&self, serial: &str) {
+        if let Some(config) = self.get_device_config(serial) {
+            self.update_collection(&config);
+        }
+    }
+
+    /// Retrieves a decoded image from the device's image collection, decoding
+    /// it on demand from [DeviceConfig::images] if it isn't cached yet, and
+    /// evicting the least-recently-used entry if the collection is over
+    /// [Self::image_cache_size] afterwards
+    pub fn get_image(&self, serial: &str, identifier: &str) -> Option<SDImage> {
+        let collection = self.get_image_collection(serial);
+
+        if let Some(image) = collection.read().unwrap().get(identifier) {
+            self.image_access_order.write().unwrap().touch(serial, identifier);
+            return Some(image.clone());
+        }
+
+        let config = self.get_device_config(serial)?;
+        let image: SDImage = {
+            let config_handle = config.read().unwrap();
+            config_handle.images.get(identifier)?.try_into().ok()?
+        };
+
+        collection.write().unwrap().insert(identifier.to_string(), image.clone());
+        self.image_access_order.write().unwrap().touch(serial, identifier);
+        self.evict_over_budget(serial, &collection);
+
+        Some(image)
+    }
+
+    /// Evicts least-recently-used images from `collection` until it's back
+    /// within [Self::image_cache_size]
+    fn evict_over_budget(&self, serial: &str, collection: &ImageCollection) {
+        let budget = self.image_cache_size();
+
+        while collection.read().unwrap().len() > budget {
+            let Some(lru_key) = self.image_access_order.write().unwrap().pop_lru(serial) else { break };
+            collection.write().unwrap().remove(&lru_key);
+        }
+    }
+
+    /// Retrieves image collection for device if device exists
+    pub fn get_image_collection(&self, serial: &str) -> ImageCollection {
+        let mut handle = self.loaded_images.write().unwrap();
+
+        if let Some(collection) = handle.get(serial) {
+            collection.clone()
+        } else {
+            let collection: ImageCollection = Default::default();
+            handle.insert(serial.to_string(), collection.clone());
+            collection
+        }
+    }
+
+    /// For making sure image collections strictly follow device config.
+    /// Images are no longer eagerly decoded here: they're pulled into the
+    /// collection on demand by [Self::get_image], which keeps memory bounded
+    /// by [Self::image_cache_size] regardless of how many images a device has
+    fn update_collection(&self, device_config: &UniqueDeviceConfig) {
+        let mut device_config = device_config.write().unwrap();
+        let mut handle = self.loaded_images.write().unwrap();
+
+        if let Some(collection) = handle.get_mut(&device_config.serial) {
+            let collection_handle = collection.read().unwrap();
+
+            // Adding any images in collection to device config
+            for (key, image) in collection_handle.iter() {
+                if !device_config.images.contains_key(key) {
+                    device_config.images.insert(key.to_string(), image.into());
+                }
+            }
+        }
+    }
+
+    /// Parses a device config from its raw JSON text, migrating it up to
+    /// [migrations::CURRENT_VERSION] first if it's older, merging in `overlay`
+    /// (the parsed `<serial>.user.json` layer, if any, see [merge::deep_merge])
+    /// and validating the result against the schema before deserializing.
+    ///
+    /// Returns the parsed, overlaid config plus the *migrated-only* base value
+    /// (without the overlay applied) if the file needs re-saving to persist the
+    /// migration. The overlay must never make it into that base value, or the
+    /// next migration-triggered re-save would permanently bake user overrides
+    /// into the base file it's meant to sit on top of.
+    fn parse_device_config(content: &str, overlay: Option<Value>) -> Result<(DeviceConfig, Option<Value>), ConfigError> {
+        let raw: Value = serde_json::from_str(content)?;
+        let version = raw.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+        let needs_migration_save = version < migrations::CURRENT_VERSION;
+
+        let migrated = migrations::migrate(raw, version);
+
+        let mut merged = migrated.clone();
+        if let Some(overlay) = overlay {
+            merge::deep_merge(&mut merged, overlay);
+        }
+
+        schema::validate(&merged).map_err(ConfigError::Validation)?;
+        let device = serde_json::from_value::<DeviceConfig>(merged)?;
+
+        Ok((device, needs_migration_save.then_some(migrated)))
+    }
+
+    /// Validates the on-disk config for `serial` (base file merged with its
+    /// user override layer, post-migration) against the [DeviceConfig] schema,
+    /// without loading it into memory. Lets a UI or CLI pre-check edits and
+    /// get back every violation, with its JSON pointer, instead of a single
+    /// opaque parse error
+    pub fn validate_device_config(&self, serial: &str) -> Result<(), Vec<ValidationIssue>> {
+        let mut path = self.device_config_path();
+        path.push(format!("{}.json", serial));
+
+        let to_issue = |message: String| vec![ValidationIssue { path: "/".to_string(), message }];
+
+        let content = fs::read_to_string(&path).map_err(|err| to_issue(err.to_string()))?;
+        let raw: Value = serde_json::from_str(&content).map_err(|err| to_issue(err.to_string()))?;
+        let version = raw.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+
+        let mut value = migrations::migrate(raw, version);
+
+        if let Some(overlay) = self.user_config_overlay(serial) {
+            merge::deep_merge(&mut value, overlay);
+        }
+
+        schema::validate(&value)
+    }
+
+    /// Reads and parses the `<serial>.user.json` override layer, if it exists.
+    /// This file is never written by [Config]; it's purely a place for a user
+    /// to hand-edit small changes that survive [Self::save_device_config]
+    /// regenerating the base file
+    fn user_config_overlay(&self, serial: &str) -> Option<Value> {
+        let mut path = self.device_config_path();
+        path.push(format!("{}.user.json", serial));
+
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// For removing images from image collections
+    fn remove_from_collection(&self, serial: &str, identifier: &str) {
+        let mut handle = self.loaded_images.write().unwrap();
+
+        if let Some(collection) = handle.get_mut(serial) {
+            let mut collection_handle = collection.write().unwrap();
+            collection_handle.remove(identifier);
+        }
+
+        self.image_access_order.write().unwrap().forget(serial, identifier);
+    }
+}
+
+/// Plugin Config trait for serialization and deserialization methods
+pub trait PluginConfig {
+    const NAME: &'static str;
+}
+
+/// Error enum for various errors while loading and parsing configs
+#[derive(Debug)]
+pub enum ConfigError {
+    IoError(std::io::Error),
+    ParseError(serde_json::Error),
+    Validation(Vec<ValidationIssue>),
+    DeviceNotFound
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigError::IoError(err)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(err: serde_json::Error) -> Self {
+        ConfigError::ParseError(err)
+    }
+}
+
+/// Device config struct
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeviceConfig {
+    /// Schema version of this config, absent on legacy files means `0`
+    #[serde(default)]
+    pub version: u32,
+    pub vid: u16,
+    pub pid: u16,
+    pub serial: String,
+    pub brightness: u8,
+    pub layout: RawButtonPanel,
+    pub images: HashMap<String, SDSerializedImage>,
+    pub plugin_data: HashMap<String, Value>,
+}
+
+impl DeviceConfig {
+    /// Gets kind of the device
+    pub fn kind(&self) -> Kind {
+        match self.pid {
+            streamdeck::pids::ORIGINAL_V2 => Kind::OriginalV2,
+            streamdeck::pids::MINI => Kind::Mini,
+            streamdeck::pids::MK2 => Kind::Mk2,
+            streamdeck::pids::XL => Kind::Xl,
+
+            _ => Kind::Original,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A legacy (pre-version) config file for `serial`, with `brightness` set
+    /// to whatever the test wants to check the migration/validation pipeline
+    /// against. Built from [DeviceConfig::default] so it stays valid against
+    /// whatever shape [RawButtonPanel] actually has, rather than hand-writing
+    /// JSON that could drift out of sync with the real struct
+    fn legacy_content(serial: &str, brightness: u64) -> String {
+        let mut value = serde_json::to_value(DeviceConfig::default()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("version");
+        object.insert("serial".to_string(), json!(serial));
+        object.insert("brightness".to_string(), json!(brightness));
+
+        serde_json::to_string(&value).unwrap()
+    }
+
+    #[test]
+    fn parse_device_config_applies_overlay_without_baking_it_into_the_resave() {
+        let content = legacy_content("ABC123", 150);
+        let overlay = json!({ "brightness": 33 });
+
+        let (device, migrated_base) = Config::parse_device_config(&content, Some(overlay)).unwrap();
+
+        // The overlay wins in the in-memory config returned to the caller...
+        assert_eq!(device.brightness, 33);
+        assert_eq!(device.version, migrations::CURRENT_VERSION);
+
+        // ...but the value written back to disk for the migration must stay
+        // overlay-free, with only the migration's own brightness clamp applied
+        let migrated_base = migrated_base.expect("legacy file should need a migration re-save");
+        assert_eq!(migrated_base["brightness"], json!(100));
+    }
+
+    #[test]
+    fn parse_device_config_skips_resave_when_already_current() {
+        let already_current = migrations::migrate(
+            serde_json::from_str(&legacy_content("ABC123", 50)).unwrap(),
+            0,
+        );
+        let content = serde_json::to_string(&already_current).unwrap();
+
+        let (_device, migrated_base) = Config::parse_device_config(&content, None).unwrap();
+
+        assert!(migrated_base.is_none());
+    }
+
+    #[test]
+    fn parse_device_config_rejects_schema_violations() {
+        let mut value = serde_json::to_value(DeviceConfig::default()).unwrap();
+        value.as_object_mut().unwrap().remove("serial");
+        let content = serde_json::to_string(&value).unwrap();
+
+        let result = Config::parse_device_config(&content, None);
+
+        assert!(matches!(result, Err(ConfigError::Validation(_))));
+    }
+
+    #[test]
+    fn forget_own_write_returns_true_exactly_once_for_a_marked_path() {
+        let config = Config::default();
+        let path = PathBuf::from("devices/ABC123.json");
+        config.mark_own_write(&path);
+
+        // The watcher's own-write suppression: the first filesystem event for a
+        // path we just wrote ourselves should be swallowed...
+        assert!(config.forget_own_write(&path));
+        // ...but `forget_own_write` also forgets it, so a later, unrelated edit
+        // to the same path isn't mistaken for our own write too
+        assert!(!config.forget_own_write(&path));
+    }
+
+    #[test]
+    fn forget_own_write_on_an_unmarked_path_is_false() {
+        let config = Config::default();
+
+        assert!(!config.forget_own_write(&PathBuf::from("devices/never-written.json")));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+//! Glob-style matching for default device config serials
+//!
+//! Default configs are keyed by a serial *pattern* rather than an exact
+//! serial, so a single file can apply to every device of a model. Only `*`
+//! (matches any serial) and prefix globs like `CL*` are supported, which
+//! covers the patterns real Stream Deck serials need.
+
+/// Returns whether `serial` is matched by `pattern`, where `pattern` is either
+/// `*` (matches everything) or a prefix glob like `CL*`
+pub fn matches(pattern: &str, serial: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+
+    match pattern.strip_suffix('*') {
+        Some(prefix) => serial.starts_with(prefix),
+        None => pattern == serial,
+    }
+}
+
+/// How specific a pattern is, as the length of its literal (non-wildcard)
+/// prefix: `"CL*"` is more specific than `"*"`, and an exact serial with no
+/// wildcard at all counts its full length, so it ties with a prefix glob made
+/// of that whole serial plus `*`. Used to pick a single, reproducible
+/// winner when more than one default pattern matches the same serial, instead
+/// of relying on incidental map iteration order
+pub fn specificity(pattern: &str) -> usize {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => prefix.len(),
+        None => pattern.len(),
+    }
+}
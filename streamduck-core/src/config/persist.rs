@@ -0,0 +1,20 @@
+//! Crash-safe file persistence
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Writes `contents` to `path` atomically: writes to a `.tmp` file next to
+/// `path`, flushes it to disk, then renames it over the target. Renaming
+/// within the same filesystem is atomic, so a crash or full disk mid-write
+/// leaves either the old file or the new one intact, never a truncated one
+pub fn atomic_write(path: &Path, contents: &str) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = Path::new(&tmp_path);
+
+    let mut file = fs::File::create(tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+
+    fs::rename(tmp_path, path)
+}
@@ -0,0 +1,54 @@
+//! JSON Schema validation for [DeviceConfig](super::DeviceConfig)
+//!
+//! Deserializing straight into [DeviceConfig] only ever reports the first
+//! field `serde_json` happens to choke on. Validating against a schema first
+//! collects every violation up front, each with a JSON pointer to the
+//! offending field, so a UI or CLI can surface precise, actionable errors
+//! instead of one opaque parse failure.
+use serde_json::{json, Value};
+
+/// JSON Schema describing a valid [DeviceConfig](super::DeviceConfig) document
+fn device_config_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["vid", "pid", "serial", "brightness", "layout", "images", "plugin_data"],
+        "properties": {
+            "version": { "type": "integer", "minimum": 0 },
+            "vid": { "type": "integer", "minimum": 0, "maximum": 65535 },
+            "pid": { "type": "integer", "minimum": 0, "maximum": 65535 },
+            "serial": { "type": "string" },
+            "brightness": { "type": "integer", "minimum": 0, "maximum": 100 },
+            "layout": { "type": "object" },
+            "images": { "type": "object" },
+            "plugin_data": { "type": "object" }
+        }
+    })
+}
+
+/// A single schema violation, with a JSON pointer to the offending field
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    /// JSON pointer to the field that failed validation, e.g. `/brightness`
+    /// (the schema only checks top-level fields)
+    pub path: String,
+    /// Human readable description of the violation
+    pub message: String,
+}
+
+/// Validates `value` against the [DeviceConfig] schema, returning every
+/// violation found, or `Ok(())` if it's valid
+pub fn validate(value: &Value) -> Result<(), Vec<ValidationIssue>> {
+    let schema = jsonschema::JSONSchema::compile(&device_config_schema())
+        .expect("device_config_schema() is a valid schema");
+
+    if let Err(errors) = schema.validate(value) {
+        let issues = errors.map(|error| ValidationIssue {
+            path: error.instance_path.to_string(),
+            message: error.to_string(),
+        }).collect();
+
+        return Err(issues);
+    }
+
+    Ok(())
+}
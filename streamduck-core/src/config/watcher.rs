@@ -0,0 +1,93 @@
+//! Background filesystem watcher for live config reloads
+//!
+//! When [Config::watch](super::Config::watch) is enabled, a background thread
+//! watches [device_config_path](super::Config::device_config_path) and
+//! [plugin_settings_path](super::Config::plugin_settings_path) and reloads
+//! whatever changed, so hand-edits to config files on disk take effect
+//! without restarting the daemon. Writes made by the config itself (via
+//! [persist::atomic_write](super::persist::atomic_write)) are tracked and
+//! skipped, so they don't trigger a pointless reload-of-what-we-just-saved.
+use std::fs;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use super::Config;
+
+/// Spawns the watcher thread if [Config::watch] is enabled, returning `None`
+/// if it's disabled or if the underlying OS watcher itself could not be
+/// created. Each watch target is set up independently and logged on failure,
+/// so a problem with one (e.g. a path that doesn't exist yet) doesn't take
+/// down the other
+pub fn spawn(config: Arc<Config>) -> Option<JoinHandle<()>> {
+    if !config.watch() {
+        return None;
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher: RecommendedWatcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            log::error!("Failed to set up config file watcher: {:?}", err);
+            return None;
+        }
+    };
+
+    watch_target(&mut watcher, &config.device_config_path(), true);
+    watch_target(&mut watcher, &config.plugin_settings_path(), false);
+
+    Some(thread::spawn(move || {
+        // Keep the watcher alive for as long as the thread runs
+        let _watcher = watcher;
+
+        for event in rx {
+            let Ok(event) = event else { continue };
+
+            for path in &event.paths {
+                if config.forget_own_write(path) {
+                    continue;
+                }
+
+                // Ignore `persist::atomic_write`'s `.tmp` scratch files and anything
+                // else that isn't one of our own JSON config files
+                if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                    continue;
+                }
+
+                if path == &config.plugin_settings_path() {
+                    config.load_plugin_settings();
+                    continue;
+                }
+
+                let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+                // A `<serial>.user.json` override edit should reload its base config
+                let serial = stem.strip_suffix(".user").unwrap_or(stem);
+
+                if let Err(err) = config.reload_device_config(serial) {
+                    log::error!("Failed to reload device config for {}: {:?}", serial, err);
+                }
+            }
+        }
+    }))
+}
+
+/// Makes sure `path` exists (`notify` can't watch a path that isn't there
+/// yet), then registers it with `watcher`, logging instead of bailing out if
+/// either step fails
+fn watch_target(watcher: &mut RecommendedWatcher, path: &std::path::Path, is_dir: bool) {
+    let create_result = if is_dir {
+        fs::create_dir_all(path)
+    } else if !path.exists() {
+        fs::write(path, "{}")
+    } else {
+        Ok(())
+    };
+
+    if let Err(err) = create_result {
+        log::error!("Failed to create {:?} for watching: {:?}", path, err);
+        return;
+    }
+
+    if let Err(err) = watcher.watch(path, RecursiveMode::NonRecursive) {
+        log::error!("Failed to watch {:?}: {:?}", path, err);
+    }
+}